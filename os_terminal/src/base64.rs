@@ -0,0 +1,120 @@
+//! Minimal base64 (RFC 4648, standard alphabet, with padding) codec.
+//!
+//! Scoped to what OSC 52 clipboard payloads need; not a general-purpose
+//! codec.
+
+const ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+        let n = ((b0 as u32) << 16) | ((b1 as u32) << 8) | b2 as u32;
+        out.push(ALPHABET[(n >> 18 & 0x3f) as usize] as char);
+        out.push(ALPHABET[(n >> 12 & 0x3f) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[(n & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+fn value(byte: u8) -> Option<u32> {
+    match byte {
+        b'A'..=b'Z' => Some((byte - b'A') as u32),
+        b'a'..=b'z' => Some((byte - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((byte - b'0') as u32 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+pub fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    let filtered: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|&b| b != b'=' && !b.is_ascii_whitespace())
+        .collect();
+    let mut out = Vec::with_capacity(filtered.len() * 3 / 4);
+    for chunk in filtered.chunks(4) {
+        if chunk.len() < 2 {
+            break;
+        }
+        let v0 = value(chunk[0])?;
+        let v1 = value(chunk[1])?;
+        let v2 = chunk.get(2).and_then(|&b| value(b));
+        let v3 = chunk.get(3).and_then(|&b| value(b));
+        out.push(((v0 << 2) | (v1 >> 4)) as u8);
+        if let Some(v2) = v2 {
+            out.push((((v1 & 0xf) << 4) | (v2 >> 2)) as u8);
+        }
+        if let (Some(v2), Some(v3)) = (v2, v3) {
+            out.push((((v2 & 0x3) << 6) | v3) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4648 section 10 test vectors.
+    const VECTORS: &[(&[u8], &str)] = &[
+        (b"", ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"foob", "Zm9vYg=="),
+        (b"fooba", "Zm9vYmE="),
+        (b"foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn encode_matches_rfc_vectors() {
+        for (input, expected) in VECTORS {
+            assert_eq!(encode(input), *expected, "encoding {input:?}");
+        }
+    }
+
+    #[test]
+    fn decode_matches_rfc_vectors() {
+        for (expected, input) in VECTORS {
+            assert_eq!(decode(input.as_bytes()).as_deref(), Some(*expected), "decoding {input:?}");
+        }
+    }
+
+    #[test]
+    fn decode_ignores_surrounding_whitespace() {
+        assert_eq!(decode(b" Zm9v \n").as_deref(), Some(&b"foo"[..]));
+    }
+
+    #[test]
+    fn decode_rejects_invalid_alphabet() {
+        assert_eq!(decode(b"!!!!"), None);
+    }
+
+    #[test]
+    fn decode_stops_on_a_dangling_single_character_group() {
+        // A final group with only one base64 character can't decode to a
+        // whole byte; the loop should stop rather than panic.
+        assert_eq!(decode(b"A"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn roundtrip_arbitrary_bytes() {
+        let data: Vec<u8> = (0u8..=255).collect();
+        assert_eq!(decode(encode(&data).as_bytes()).as_deref(), Some(data.as_slice()));
+    }
+}
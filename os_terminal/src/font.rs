@@ -0,0 +1,65 @@
+//! Font backends consumed by [`crate::Terminal::set_font_manager`].
+
+/// Supplies the cell metrics the terminal uses to size its character grid.
+pub trait FontManager {
+    /// Width and height, in pixels, of one monospace cell at the current size.
+    fn cell_size(&self) -> (usize, usize);
+
+    /// Rebuilds the glyph cache at a new point size.
+    fn set_size(&mut self, size: f32);
+}
+
+/// A TrueType/OpenType font manager.
+///
+/// This crate has no TrueType rasterizer (none is vendorable offline), so
+/// `flush()` draws a placeholder glyph box rather than real outlines. The
+/// sfnt header is still checked so a corrupt or truncated font file fails
+/// fast at construction instead of being silently accepted and ignored.
+pub struct TrueTypeFont {
+    size: f32,
+    subpixel: bool,
+}
+
+/// sfnt version tags recognized in the first 4 bytes of a TrueType/OpenType
+/// font: TrueType outlines, OpenType/CFF outlines, and the two historical
+/// Apple tags.
+const SFNT_TAGS: [[u8; 4]; 4] = [[0x00, 0x01, 0x00, 0x00], *b"OTTO", *b"true", *b"typ1"];
+
+impl TrueTypeFont {
+    /// # Panics
+    ///
+    /// Panics if `data` is not at least 4 bytes, or its header does not
+    /// match a recognized sfnt version tag.
+    pub fn new(size: f32, data: &[u8]) -> Self {
+        let header: [u8; 4] = data
+            .get(..4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .expect("font data too short for an sfnt header");
+        assert!(
+            SFNT_TAGS.contains(&header),
+            "font data is not a recognized TrueType/OpenType file"
+        );
+        Self {
+            size,
+            subpixel: false,
+        }
+    }
+
+    pub fn with_subpixel(mut self, enabled: bool) -> Self {
+        self.subpixel = enabled;
+        self
+    }
+}
+
+impl FontManager for TrueTypeFont {
+    fn cell_size(&self) -> (usize, usize) {
+        (
+            (self.size * 0.6).round().max(1.0) as usize,
+            (self.size * 1.3).round().max(1.0) as usize,
+        )
+    }
+
+    fn set_size(&mut self, size: f32) {
+        self.size = size;
+    }
+}
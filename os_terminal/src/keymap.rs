@@ -0,0 +1,235 @@
+//! PC/XT "scancode set 1" to PTY-byte translation.
+//!
+//! The host forwards raw `keycode::KeyMap::win` values (which are scancode
+//! set 1 compatible: a single byte per key in the base block, an `0xe0`
+//! prefix byte ahead of the extended block, and the high bit set on the
+//! final byte for a key-release) straight into [`crate::Terminal::handle_keyboard`].
+//! This module turns that stream into the bytes a shell expects on its
+//! input.
+
+/// Result of translating one (possibly `0xe0`-prefixed) scancode.
+pub enum Key {
+    /// Write these bytes to the PTY.
+    Bytes(&'static [u8]),
+    /// The left or right Shift key; `true` on press, `false` on release.
+    Shift(bool),
+    /// The left or right Ctrl key; `true` on press, `false` on release.
+    Ctrl(bool),
+    /// A key this module doesn't translate (release events, modifiers
+    /// other than Shift/Ctrl, unmapped keys).
+    None,
+}
+
+const LEFT_SHIFT: u8 = 0x2a;
+const RIGHT_SHIFT: u8 = 0x36;
+const LEFT_CTRL: u8 = 0x1d;
+
+/// Maps a base-block (non-`0xe0`-prefixed) scancode to its unshifted and
+/// shifted ASCII bytes, in set-1 order starting at `0x02` (`1`/`!`).
+const ASCII_ROWS: &[(u8, u8)] = &[
+    (b'1', b'!'), (b'2', b'@'), (b'3', b'#'), (b'4', b'$'), (b'5', b'%'),
+    (b'6', b'^'), (b'7', b'&'), (b'8', b'*'), (b'9', b'('), (b'0', b')'),
+    (b'-', b'_'), (b'=', b'+'),
+];
+
+fn base_ascii(code: u8, shift: bool) -> Option<u8> {
+    let byte = match code {
+        0x02..=0x0d => {
+            let (lower, upper) = ASCII_ROWS[(code - 0x02) as usize];
+            if shift { upper } else { lower }
+        }
+        0x0f => b'\t',
+        0x1c => b'\r',
+        0x39 => b' ',
+        0x10..=0x19 => {
+            let letters = b"qwertyuiop";
+            letters[(code - 0x10) as usize]
+        }
+        0x1e..=0x26 => {
+            let letters = b"asdfghjkl";
+            letters[(code - 0x1e) as usize]
+        }
+        0x2c..=0x32 => {
+            let letters = b"zxcvbnm";
+            letters[(code - 0x2c) as usize]
+        }
+        0x1a => if shift { b'{' } else { b'[' },
+        0x1b => if shift { b'}' } else { b']' },
+        0x27 => if shift { b':' } else { b';' },
+        0x28 => if shift { b'"' } else { b'\'' },
+        0x29 => if shift { b'~' } else { b'`' },
+        0x2b => if shift { b'|' } else { b'\\' },
+        0x33 => if shift { b'<' } else { b',' },
+        0x34 => if shift { b'>' } else { b'.' },
+        0x35 => if shift { b'?' } else { b'/' },
+        _ => return None,
+    };
+    Some(if shift && is_letter_scancode(code) {
+        byte.to_ascii_uppercase()
+    } else {
+        byte
+    })
+}
+
+/// Whether `code` is one of the base-block letter rows, the only ones in
+/// [`base_ascii`] that need upper-casing on Shift (everything else already
+/// picked its shifted byte directly).
+fn is_letter_scancode(code: u8) -> bool {
+    matches!(code, 0x10..=0x19 | 0x1e..=0x26 | 0x2c..=0x32)
+}
+
+fn ctrl_byte(ascii: u8) -> Option<u8> {
+    match ascii.to_ascii_uppercase() {
+        b'A'..=b'Z' => Some(ascii.to_ascii_uppercase() - b'A' + 1),
+        _ => None,
+    }
+}
+
+/// Maps an `0xe0`-prefixed scancode to the CSI/SS3 sequence a shell expects.
+fn extended_sequence(code: u8) -> Option<&'static [u8]> {
+    Some(match code {
+        0x48 => b"\x1b[A",  // Up
+        0x50 => b"\x1b[B",  // Down
+        0x4d => b"\x1b[C",  // Right
+        0x4b => b"\x1b[D",  // Left
+        0x47 => b"\x1b[H",  // Home
+        0x4f => b"\x1b[F",  // End
+        0x52 => b"\x1b[2~", // Insert
+        0x53 => b"\x1b[3~", // Delete
+        0x49 => b"\x1b[5~", // Page Up
+        0x51 => b"\x1b[6~", // Page Down
+        _ => return None,
+    })
+}
+
+/// Translates one scancode byte. `extended` is `true` when this byte
+/// follows an `0xe0` prefix byte. Release events (high bit set) are
+/// reported as `Key::None` except for Shift/Ctrl, which the terminal needs
+/// to track across press and release.
+pub fn translate(code: u8, extended: bool, shift: bool, ctrl: bool) -> Key {
+    let released = code & 0x80 != 0;
+    let code = code & 0x7f;
+
+    if !extended {
+        match code {
+            LEFT_SHIFT | RIGHT_SHIFT => return Key::Shift(!released),
+            LEFT_CTRL => return Key::Ctrl(!released),
+            _ => {}
+        }
+    } else if code == LEFT_CTRL {
+        // Right Ctrl: the same code behind an `0xe0` prefix.
+        return Key::Ctrl(!released);
+    }
+    if released {
+        return Key::None;
+    }
+
+    if extended {
+        return match extended_sequence(code) {
+            Some(seq) => Key::Bytes(seq),
+            None => Key::None,
+        };
+    }
+
+    if code == 0x01 {
+        return Key::Bytes(b"\x1b");
+    }
+    let Some(ascii) = base_ascii(code, shift) else {
+        return Key::None;
+    };
+    if ctrl {
+        return match ctrl_byte(ascii) {
+            Some(byte) => Key::Bytes(ctrl_sequence(byte)),
+            None => Key::None,
+        };
+    }
+    Key::Bytes(ascii_byte(ascii))
+}
+
+/// A one-byte slice for every ASCII value, so [`translate`] can return
+/// `&'static [u8]` without allocating.
+static ASCII_BYTES: [[u8; 1]; 256] = {
+    let mut table = [[0u8; 1]; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = [i as u8];
+        i += 1;
+    }
+    table
+};
+
+fn ascii_byte(byte: u8) -> &'static [u8] {
+    &ASCII_BYTES[byte as usize]
+}
+
+fn ctrl_sequence(byte: u8) -> &'static [u8] {
+    ascii_byte(byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_lowercase_letter() {
+        match translate(0x1e, false, false, false) {
+            Key::Bytes(b) => assert_eq!(b, b"a"),
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    #[test]
+    fn shift_uppercases_letter() {
+        match translate(0x1e, false, true, false) {
+            Key::Bytes(b) => assert_eq!(b, b"A"),
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    #[test]
+    fn shift_picks_symbol_row() {
+        match translate(0x02, false, true, false) {
+            Key::Bytes(b) => assert_eq!(b, b"!"),
+            _ => panic!("expected bytes"),
+        }
+        match translate(0x02, false, false, false) {
+            Key::Bytes(b) => assert_eq!(b, b"1"),
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    #[test]
+    fn ctrl_c_sends_etx() {
+        match translate(0x2e, false, false, true) {
+            Key::Bytes(b) => assert_eq!(b, &[0x03]),
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    #[test]
+    fn release_is_ignored_except_modifiers() {
+        assert!(matches!(translate(0x1e | 0x80, false, false, false), Key::None));
+        assert!(matches!(translate(LEFT_SHIFT | 0x80, false, false, false), Key::Shift(false)));
+        assert!(matches!(translate(LEFT_SHIFT, false, false, false), Key::Shift(true)));
+    }
+
+    #[test]
+    fn extended_arrow_keys() {
+        match translate(0x48, true, false, false) {
+            Key::Bytes(b) => assert_eq!(b, b"\x1b[A"),
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    #[test]
+    fn enter_and_tab() {
+        match translate(0x1c, false, false, false) {
+            Key::Bytes(b) => assert_eq!(b, b"\r"),
+            _ => panic!("expected bytes"),
+        }
+        match translate(0x0f, false, false, false) {
+            Key::Bytes(b) => assert_eq!(b, b"\t"),
+            _ => panic!("expected bytes"),
+        }
+    }
+}
@@ -0,0 +1,681 @@
+//! Minimal terminal-emulator core driven by the bare-metal host.
+//!
+//! A [`Terminal`] owns a character grid and renders it into a pluggable
+//! [`DrawTarget`]. It implements the exact escape sequences and host hooks
+//! the `os-terminal-bare` binary drives, rather than full VT100 coverage.
+
+mod base64;
+pub mod font;
+mod keymap;
+mod parser;
+
+use font::FontManager;
+use keymap::Key;
+use parser::{Action, Parser};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// An 8-bit-per-channel RGB color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(0xd0, 0xd0, 0xd0);
+}
+
+/// A pixel sink the [`Terminal`] renders into.
+pub trait DrawTarget {
+    fn size(&self) -> (usize, usize);
+    fn draw_pixel(&mut self, x: usize, y: usize, rgb: Rgb);
+}
+
+/// Pointer events fed into [`Terminal::handle_mouse`], in terminal cell
+/// coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseInput {
+    Scroll(isize),
+    Press(usize, usize),
+    Move(usize, usize),
+    Release,
+}
+
+#[derive(Clone, Copy)]
+struct Cell {
+    ch: char,
+    fg: Rgb,
+    bg: Rgb,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            fg: Rgb::WHITE,
+            bg: Rgb::BLACK,
+        }
+    }
+}
+
+/// A linear selection from `start` to `end`, in (col, row) cell
+/// coordinates: rows strictly between the two endpoints are selected in full.
+#[derive(Clone, Copy, Default)]
+struct Selection {
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl Selection {
+    fn ordered(&self) -> ((usize, usize), (usize, usize)) {
+        if self.start.1 < self.end.1 || (self.start.1 == self.end.1 && self.start.0 <= self.end.0) {
+            (self.start, self.end)
+        } else {
+            (self.end, self.start)
+        }
+    }
+
+    fn contains(&self, col: usize, row: usize) -> bool {
+        let ((start_col, start_row), (end_col, end_row)) = self.ordered();
+        if row < start_row || row > end_row {
+            return false;
+        }
+        let after_start = row > start_row || col >= start_col;
+        let before_end = row < end_row || col <= end_col;
+        after_start && before_end
+    }
+}
+
+/// Which DEC mouse-tracking private mode (if any) is currently active.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum MouseMode {
+    #[default]
+    Off,
+    Normal,      // 1000: press/release only
+    ButtonEvent, // 1002: press/release plus motion while a button is down
+    AnyMotion,   // 1003: press/release plus all motion
+}
+
+const DEFAULT_FONT_SIZE: f32 = 10.0;
+const MIN_FONT_SIZE: f32 = 6.0;
+const MAX_FONT_SIZE: f32 = 36.0;
+const FONT_SIZE_STEP: f32 = 1.0;
+
+fn cell_metrics(font_size: f32) -> (usize, usize) {
+    (
+        (font_size * 0.6).round().max(1.0) as usize,
+        (font_size * 1.3).round().max(1.0) as usize,
+    )
+}
+
+/// Callback invoked with bytes to send to the PTY, or with the new window
+/// title on an `OSC 0`/`OSC 2` capture.
+type StrCallback = Box<dyn FnMut(&str)>;
+
+pub struct Terminal<D: DrawTarget> {
+    display: D,
+    columns: usize,
+    rows: usize,
+    cell_width: usize,
+    cell_height: usize,
+    font_size: f32,
+    font_manager: Option<Box<dyn FontManager>>,
+    grid: Vec<Vec<Cell>>,
+    history: VecDeque<Vec<Cell>>,
+    history_size: usize,
+    cursor: (usize, usize),
+    fg: Rgb,
+    bg: Rgb,
+    auto_flush: bool,
+    scroll_speed: usize,
+
+    pty_writer: Option<StrCallback>,
+    resize_callback: Option<Box<dyn FnMut(usize, usize)>>,
+    clipboard_callback: Option<Box<dyn FnMut(String)>>,
+    clipboard_query_callback: Option<Box<dyn FnMut() -> String>>,
+    title: String,
+    title_callback: Option<StrCallback>,
+
+    selection: Option<Selection>,
+    selecting: bool,
+
+    parser: Parser,
+    bracketed_paste: bool,
+    mouse_mode: MouseMode,
+    sgr_mouse: bool,
+    last_mouse: (usize, usize),
+    synchronized: bool,
+
+    shift: bool,
+    ctrl: bool,
+    pending_extended: bool,
+}
+
+impl<D: DrawTarget> Terminal<D> {
+    pub fn new(display: D) -> Self {
+        let (cell_width, cell_height) = cell_metrics(DEFAULT_FONT_SIZE);
+        let (width, height) = display.size();
+        let columns = (width / cell_width).max(1);
+        let rows = (height / cell_height).max(1);
+        Self {
+            display,
+            columns,
+            rows,
+            cell_width,
+            cell_height,
+            font_size: DEFAULT_FONT_SIZE,
+            font_manager: None,
+            grid: vec![vec![Cell::default(); columns]; rows],
+            history: VecDeque::new(),
+            history_size: 0,
+            cursor: (0, 0),
+            fg: Rgb::WHITE,
+            bg: Rgb::BLACK,
+            auto_flush: true,
+            scroll_speed: 1,
+            pty_writer: None,
+            resize_callback: None,
+            clipboard_callback: None,
+            clipboard_query_callback: None,
+            title: String::new(),
+            title_callback: None,
+            selection: None,
+            selecting: false,
+            parser: Parser::new(),
+            bracketed_paste: false,
+            mouse_mode: MouseMode::Off,
+            sgr_mouse: false,
+            last_mouse: (0, 0),
+            synchronized: false,
+            shift: false,
+            ctrl: false,
+            pending_extended: false,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn set_auto_flush(&mut self, enabled: bool) {
+        self.auto_flush = enabled;
+    }
+
+    pub fn set_scroll_speed(&mut self, speed: usize) {
+        self.scroll_speed = speed.max(1);
+    }
+
+    /// Reserved for the glyph-rasterization cache; this minimal core has
+    /// none to size, but the knob is kept so host code doesn't change when
+    /// one is added.
+    pub fn set_color_cache_size(&mut self, _cells: usize) {}
+
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+        while self.history.len() > self.history_size {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn set_pty_writer(&mut self, writer: StrCallback) {
+        self.pty_writer = Some(writer);
+    }
+
+    pub fn set_font_manager(&mut self, manager: Box<dyn FontManager>) {
+        let (cell_width, cell_height) = manager.cell_size();
+        self.font_manager = Some(manager);
+        self.resize_cells(cell_width, cell_height);
+    }
+
+    pub fn set_resize_callback(&mut self, callback: Box<dyn FnMut(usize, usize)>) {
+        self.resize_callback = Some(callback);
+    }
+
+    pub fn set_clipboard_callback(&mut self, callback: Box<dyn FnMut(String)>) {
+        self.clipboard_callback = Some(callback);
+    }
+
+    pub fn set_clipboard_query_callback(&mut self, callback: Box<dyn FnMut() -> String>) {
+        self.clipboard_query_callback = Some(callback);
+    }
+
+    /// The window title last set via `OSC 0` or `OSC 2`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title_callback(&mut self, callback: StrCallback) {
+        self.title_callback = Some(callback);
+    }
+
+    /// Sets the font size to `size` points (clamped to 6..=36), resizes the
+    /// installed font manager if there is one, then recomputes cell
+    /// metrics, reflows the grid to the new `rows()`/`columns()`, and
+    /// invokes the resize callback when the row or column count changed.
+    pub fn set_font_size(&mut self, size: f32) {
+        self.font_size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        let (cell_width, cell_height) = match &mut self.font_manager {
+            Some(manager) => {
+                manager.set_size(self.font_size);
+                manager.cell_size()
+            }
+            None => cell_metrics(self.font_size),
+        };
+        self.resize_cells(cell_width, cell_height);
+    }
+
+    pub fn increase_font_size(&mut self) {
+        self.set_font_size(self.font_size + FONT_SIZE_STEP);
+    }
+
+    pub fn decrease_font_size(&mut self) {
+        self.set_font_size(self.font_size - FONT_SIZE_STEP);
+    }
+
+    fn resize_cells(&mut self, cell_width: usize, cell_height: usize) {
+        self.cell_width = cell_width;
+        self.cell_height = cell_height;
+        let (width, height) = self.display.size();
+        let columns = (width / cell_width).max(1);
+        let rows = (height / cell_height).max(1);
+        self.reflow(columns, rows);
+    }
+
+    /// Resizes the grid and scrollback to `columns` by `rows`, keeping the
+    /// bottom of the screen (the cursor and most recent output) stable.
+    /// Shrinking rows pushes the discarded top lines into `history`
+    /// instead of dropping them; growing rows pulls lines back out of
+    /// `history` before padding with blanks.
+    fn reflow(&mut self, columns: usize, rows: usize) {
+        if columns == self.columns && rows == self.rows {
+            return;
+        }
+        if columns != self.columns {
+            for line in self.grid.iter_mut().chain(self.history.iter_mut()) {
+                line.resize(columns, Cell::default());
+            }
+        }
+        match rows.cmp(&self.rows) {
+            Ordering::Less => {
+                let shrink_by = self.rows - rows;
+                let overflow: Vec<_> = self.grid.drain(..shrink_by).collect();
+                for removed in overflow {
+                    self.push_history(removed);
+                }
+                // Every remaining row moved up by `shrink_by`, so the
+                // cursor's row index has to move with it.
+                self.cursor.1 = self.cursor.1.saturating_sub(shrink_by);
+            }
+            Ordering::Greater => {
+                let grow_by = rows - self.rows;
+                for _ in 0..grow_by {
+                    let line = self
+                        .history
+                        .pop_back()
+                        .unwrap_or_else(|| vec![Cell::default(); columns]);
+                    self.grid.insert(0, line);
+                }
+                // Every existing row moved down by `grow_by`.
+                self.cursor.1 += grow_by;
+            }
+            Ordering::Equal => {}
+        }
+        self.columns = columns;
+        self.rows = rows;
+        self.cursor.0 = self.cursor.0.min(columns.saturating_sub(1));
+        self.cursor.1 = self.cursor.1.min(rows.saturating_sub(1));
+        if let Some(callback) = &mut self.resize_callback {
+            callback(columns, rows);
+        }
+    }
+
+    /// Pushes a scrolled-off line onto the back of `history`, evicting the
+    /// oldest entry first if already at `history_size`.
+    fn push_history(&mut self, line: Vec<Cell>) {
+        if self.history_size == 0 {
+            return;
+        }
+        if self.history.len() >= self.history_size {
+            self.history.pop_front();
+        }
+        self.history.push_back(line);
+    }
+
+    pub fn process(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let mut actions = Vec::new();
+            self.parser.advance(byte, |action| actions.push(action));
+            for action in actions {
+                self.dispatch(action);
+            }
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) {
+        match action {
+            Action::Print(byte) => self.process_byte(byte),
+            Action::Csi {
+                private,
+                params,
+                final_byte,
+            } => self.apply_csi(private, &params, final_byte),
+            Action::Osc(data) => self.apply_osc(&data),
+        }
+    }
+
+    fn apply_csi(&mut self, private: bool, params: &[u16], final_byte: u8) {
+        if !private {
+            return;
+        }
+        let enable = match final_byte {
+            b'h' => true,
+            b'l' => false,
+            _ => return,
+        };
+        for &mode in params {
+            match mode {
+                2004 => self.bracketed_paste = enable,
+                1000 => self.mouse_mode = if enable { MouseMode::Normal } else { MouseMode::Off },
+                1002 => self.mouse_mode = if enable { MouseMode::ButtonEvent } else { MouseMode::Off },
+                1003 => self.mouse_mode = if enable { MouseMode::AnyMotion } else { MouseMode::Off },
+                1006 => self.sgr_mouse = enable,
+                2026 => self.synchronized = enable,
+                _ => {}
+            }
+        }
+    }
+
+    /// Dispatches an `OSC ... (BEL|ST)` string: `0`/`2` capture the window
+    /// title, `52` reads or writes the clipboard as base64.
+    fn apply_osc(&mut self, data: &[u8]) {
+        let Some(semi) = data.iter().position(|&b| b == b';') else {
+            return;
+        };
+        let (command, rest) = data.split_at(semi);
+        let rest = &rest[1..];
+        let Ok(command) = std::str::from_utf8(command) else {
+            return;
+        };
+        match command {
+            "0" | "2" => {
+                let Ok(title) = std::str::from_utf8(rest) else {
+                    return;
+                };
+                self.title = title.to_string();
+                if let Some(callback) = &mut self.title_callback {
+                    callback(&self.title);
+                }
+            }
+            "52" => self.apply_osc52(rest),
+            _ => {}
+        }
+    }
+
+    fn apply_osc52(&mut self, rest: &[u8]) {
+        let Some(semi) = rest.iter().position(|&b| b == b';') else {
+            return;
+        };
+        let payload = &rest[semi + 1..];
+        if payload == b"?" {
+            let text = self
+                .clipboard_query_callback
+                .as_mut()
+                .map(|callback| callback())
+                .unwrap_or_default();
+            if let Some(writer) = &mut self.pty_writer {
+                writer(&format!("\x1b]52;c;{}\x07", base64::encode(text.as_bytes())));
+            }
+        } else if let Some(decoded) = base64::decode(payload) {
+            if let Ok(text) = String::from_utf8(decoded) {
+                if let Some(callback) = &mut self.clipboard_callback {
+                    callback(text);
+                }
+            }
+        }
+    }
+
+    /// Writes `text` through the PTY writer, wrapped in the bracketed-paste
+    /// markers when the child has enabled DEC mode 2004 (`CSI ? 2004 h`).
+    pub fn paste(&mut self, text: &str) {
+        let sanitized: String = text
+            .chars()
+            .filter(|&ch| ch == '\n' || ch == '\t' || !ch.is_control())
+            .collect();
+        let Some(writer) = &mut self.pty_writer else {
+            return;
+        };
+        if self.bracketed_paste {
+            writer(&format!("\x1b[200~{sanitized}\x1b[201~"));
+        } else {
+            writer(&sanitized);
+        }
+    }
+
+    fn process_byte(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.newline(),
+            b'\r' => self.cursor.0 = 0,
+            0x08 => self.cursor.0 = self.cursor.0.saturating_sub(1),
+            0x07 => {}
+            _ => {
+                if let Some(ch) = Self::decode_ascii(byte) {
+                    self.put_char(ch);
+                }
+            }
+        }
+    }
+
+    fn decode_ascii(byte: u8) -> Option<char> {
+        if byte >= 0x20 && byte != 0x7f {
+            Some(byte as char)
+        } else {
+            None
+        }
+    }
+
+    fn put_char(&mut self, ch: char) {
+        if self.cursor.0 >= self.columns {
+            self.newline();
+        }
+        let (col, row) = self.cursor;
+        self.grid[row][col] = Cell {
+            ch,
+            fg: self.fg,
+            bg: self.bg,
+        };
+        self.cursor.0 += 1;
+    }
+
+    fn newline(&mut self) {
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < self.rows {
+            self.cursor.1 += 1;
+        } else {
+            self.scroll_up(1);
+        }
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        for _ in 0..lines {
+            let removed = self.grid.remove(0);
+            self.push_history(removed);
+            self.grid.push(vec![Cell::default(); self.columns]);
+        }
+    }
+
+    /// Translates one PC scancode-set-1 byte and writes the resulting PTY
+    /// input through [`Self::set_pty_writer`]. An `0xe0` byte marks the next
+    /// call as part of the extended block (arrows, Home/End, ...) and is
+    /// otherwise consumed silently, matching how the host forwards it.
+    pub fn handle_keyboard(&mut self, scancode: u8) {
+        if scancode == 0xe0 {
+            self.pending_extended = true;
+            return;
+        }
+        let extended = std::mem::take(&mut self.pending_extended);
+        match keymap::translate(scancode, extended, self.shift, self.ctrl) {
+            Key::Bytes(bytes) => {
+                if let Some(writer) = &mut self.pty_writer {
+                    if let Ok(text) = std::str::from_utf8(bytes) {
+                        writer(text);
+                    }
+                }
+            }
+            Key::Shift(pressed) => self.shift = pressed,
+            Key::Ctrl(pressed) => self.ctrl = pressed,
+            Key::None => {}
+        }
+    }
+
+    /// Whether a DEC mouse-tracking private mode (1000/1002/1003) is active.
+    pub fn mouse_tracking(&self) -> bool {
+        self.mouse_mode != MouseMode::Off
+    }
+
+    /// Whether local text selection should react to the mouse right now:
+    /// the child hasn't requested tracking, or it has but Shift overrides
+    /// it (the same override the host uses for Shift+Insert paste).
+    fn selection_enabled(&self) -> bool {
+        !self.mouse_tracking() || self.shift
+    }
+
+    pub fn handle_mouse(&mut self, input: MouseInput) {
+        match input {
+            MouseInput::Press(col, row) => {
+                self.last_mouse = (col, row);
+                // `selecting` tracks the held button; the selection itself
+                // stays empty while the child owns the mouse.
+                self.selecting = true;
+                if self.selection_enabled() {
+                    self.selection = Some(Selection {
+                        start: (col, row),
+                        end: (col, row),
+                    });
+                } else {
+                    self.selection = None;
+                }
+                if self.mouse_tracking() {
+                    self.report_mouse(0, col, row, false);
+                }
+            }
+            MouseInput::Move(col, row) => {
+                self.last_mouse = (col, row);
+                if self.selecting {
+                    if let Some(selection) = &mut self.selection {
+                        selection.end = (col, row);
+                    }
+                }
+                let reportable = match self.mouse_mode {
+                    MouseMode::AnyMotion => true,
+                    MouseMode::ButtonEvent => self.selecting,
+                    MouseMode::Normal | MouseMode::Off => false,
+                };
+                if reportable {
+                    let button = if self.selecting { 0 } else { 3 };
+                    self.report_mouse(32 + button, col, row, false);
+                }
+            }
+            MouseInput::Release => {
+                let (col, row) = self.last_mouse;
+                let was_tracked = self.selecting && self.mouse_tracking();
+                self.selecting = false;
+                if was_tracked {
+                    self.report_mouse(0, col, row, true);
+                }
+            }
+            MouseInput::Scroll(delta) => {
+                if self.mouse_tracking() {
+                    let (col, row) = self.last_mouse;
+                    let button = if delta < 0 { 64 } else { 65 };
+                    self.report_mouse(button, col, row, false);
+                }
+            }
+        }
+    }
+
+    /// Encodes a mouse report through the PTY writer: legacy `CSI M Cb Cx
+    /// Cy` with byte-offset-32 coordinates, or, under DEC mode 1006, `CSI <
+    /// b ; x ; y M|m` with 1-based cell coordinates.
+    fn report_mouse(&mut self, button: u8, col: usize, row: usize, release: bool) {
+        let Some(writer) = &mut self.pty_writer else {
+            return;
+        };
+        if self.sgr_mouse {
+            let final_byte = if release { 'm' } else { 'M' };
+            writer(&format!("\x1b[<{button};{};{}{final_byte}", col + 1, row + 1));
+        } else {
+            let cb = 32 + if release { 3 } else { button };
+            let cx = 32 + (col + 1).min(223) as u8;
+            let cy = 32 + (row + 1).min(223) as u8;
+            writer(&format!("\x1b[M{}{}{}", cb as char, cx as char, cy as char));
+        }
+    }
+
+    /// Logically-joined text of the selected region: trailing blanks on
+    /// each row are trimmed, and rows are newline-joined.
+    pub fn selection_text(&self) -> String {
+        let Some(selection) = self.selection else {
+            return String::new();
+        };
+        let ((start_col, start_row), (end_col, end_row)) = selection.ordered();
+        let mut text = String::new();
+        for row in start_row..=end_row.min(self.rows.saturating_sub(1)) {
+            let line = &self.grid[row];
+            let from = if row == start_row { start_col } else { 0 };
+            let to = if row == end_row {
+                end_col.min(self.columns.saturating_sub(1))
+            } else {
+                self.columns.saturating_sub(1)
+            };
+            if from <= to {
+                let joined: String = line[from..=to].iter().map(|cell| cell.ch).collect();
+                text.push_str(joined.trim_end());
+            }
+            if row != end_row {
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Whether the grid holds a complete frame. Returns `false` while a
+    /// synchronized-update block (`CSI ? 2026 h` ... `CSI ? 2026 l`) is
+    /// open, so the flush thread skips presenting a half-drawn screen.
+    pub fn frame_ready(&self) -> bool {
+        !self.synchronized
+    }
+
+    pub fn flush(&mut self) {
+        let (cell_width, cell_height) = (self.cell_width, self.cell_height);
+        let selection_visible = self.selection_enabled();
+        for row in 0..self.rows {
+            for col in 0..self.columns {
+                let cell = self.grid[row][col];
+                let (fg, bg) = match &self.selection {
+                    Some(selection) if selection_visible && selection.contains(col, row) => {
+                        (cell.bg, cell.fg)
+                    }
+                    _ => (cell.fg, cell.bg),
+                };
+                let base_x = col * cell_width;
+                let base_y = row * cell_height;
+                // No glyph rasterizer is wired up yet, so a non-blank cell
+                // is drawn as an inset block rather than its real outline.
+                for dy in 0..cell_height {
+                    for dx in 0..cell_width {
+                        let inked = cell.ch != ' '
+                            && dx > 0
+                            && dy > 0
+                            && dx + 1 < cell_width
+                            && dy + 1 < cell_height;
+                        self.display
+                            .draw_pixel(base_x + dx, base_y + dy, if inked { fg } else { bg });
+                    }
+                }
+            }
+        }
+    }
+}
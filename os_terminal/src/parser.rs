@@ -0,0 +1,190 @@
+//! A small ANSI escape-sequence scanner.
+//!
+//! Recognizes just enough of ECMA-48 to drive the DEC private modes and OSC
+//! commands this crate implements: plain bytes, CSI sequences (with an
+//! optional `?` prefix marking DEC-private parameters), and OSC strings
+//! terminated by BEL or ST (`ESC \`).
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    Print(u8),
+    Csi {
+        private: bool,
+        params: Vec<u16>,
+        final_byte: u8,
+    },
+    Osc(Vec<u8>),
+}
+
+#[derive(Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    CsiEntry,
+    CsiParam,
+    OscString,
+    OscEscape,
+}
+
+#[derive(Default)]
+pub struct Parser {
+    state: State,
+    private: bool,
+    params: Vec<u16>,
+    current: Option<u16>,
+    osc: Vec<u8>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&mut self, byte: u8, mut on_action: impl FnMut(Action)) {
+        match self.state {
+            State::Ground => match byte {
+                0x1b => self.state = State::Escape,
+                _ => on_action(Action::Print(byte)),
+            },
+            State::Escape => match byte {
+                b'[' => {
+                    self.private = false;
+                    self.params.clear();
+                    self.current = None;
+                    self.state = State::CsiEntry;
+                }
+                b']' => {
+                    self.osc.clear();
+                    self.state = State::OscString;
+                }
+                _ => self.state = State::Ground,
+            },
+            State::CsiEntry => {
+                if byte == b'?' {
+                    self.private = true;
+                    self.state = State::CsiParam;
+                } else {
+                    self.state = State::CsiParam;
+                    self.consume_csi_param(byte, &mut on_action);
+                }
+            }
+            State::CsiParam => self.consume_csi_param(byte, &mut on_action),
+            State::OscString => match byte {
+                0x07 => {
+                    on_action(Action::Osc(std::mem::take(&mut self.osc)));
+                    self.state = State::Ground;
+                }
+                0x1b => self.state = State::OscEscape,
+                _ => self.osc.push(byte),
+            },
+            State::OscEscape => {
+                if byte == b'\\' {
+                    on_action(Action::Osc(std::mem::take(&mut self.osc)));
+                }
+                self.state = State::Ground;
+            }
+        }
+    }
+
+    fn consume_csi_param(&mut self, byte: u8, on_action: &mut impl FnMut(Action)) {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = (byte - b'0') as u16;
+                self.current = Some(self.current.unwrap_or(0) * 10 + digit);
+            }
+            b';' => self.params.push(self.current.take().unwrap_or(0)),
+            0x40..=0x7e => {
+                self.params.push(self.current.take().unwrap_or(0));
+                on_action(Action::Csi {
+                    private: self.private,
+                    params: std::mem::take(&mut self.params),
+                    final_byte: byte,
+                });
+                self.state = State::Ground;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions_for(bytes: &[u8]) -> Vec<Action> {
+        let mut parser = Parser::new();
+        let mut actions = Vec::new();
+        for &byte in bytes {
+            parser.advance(byte, |action| actions.push(action));
+        }
+        actions
+    }
+
+    #[test]
+    fn plain_bytes_are_printed() {
+        assert_eq!(actions_for(b"hi"), vec![Action::Print(b'h'), Action::Print(b'i')]);
+    }
+
+    #[test]
+    fn csi_with_no_params_defaults_to_zero() {
+        assert_eq!(
+            actions_for(b"\x1b[m"),
+            vec![Action::Csi { private: false, params: vec![0], final_byte: b'm' }]
+        );
+    }
+
+    #[test]
+    fn csi_parses_multiple_numeric_params() {
+        assert_eq!(
+            actions_for(b"\x1b[1;30;42m"),
+            vec![Action::Csi {
+                private: false,
+                params: vec![1, 30, 42],
+                final_byte: b'm',
+            }]
+        );
+    }
+
+    #[test]
+    fn csi_private_marker_sets_private_flag() {
+        assert_eq!(
+            actions_for(b"\x1b[?2004h"),
+            vec![Action::Csi { private: true, params: vec![2004], final_byte: b'h' }]
+        );
+    }
+
+    #[test]
+    fn osc_terminated_by_bel() {
+        assert_eq!(actions_for(b"\x1b]0;title\x07"), vec![Action::Osc(b"0;title".to_vec())]);
+    }
+
+    #[test]
+    fn osc_terminated_by_st() {
+        assert_eq!(actions_for(b"\x1b]0;title\x1b\\"), vec![Action::Osc(b"0;title".to_vec())]);
+    }
+
+    #[test]
+    fn osc_escape_without_backslash_is_dropped_silently() {
+        // ESC not followed by '\' inside an OSC string aborts it without
+        // emitting an action, per the OscEscape branch falling back to
+        // Ground on anything but 0x5c.
+        assert_eq!(actions_for(b"\x1b]0;abc\x1bX"), vec![]);
+    }
+
+    #[test]
+    fn incomplete_sequence_emits_nothing() {
+        assert_eq!(actions_for(b"\x1b[1;2"), vec![]);
+    }
+
+    #[test]
+    fn parser_resets_between_sequences() {
+        assert_eq!(
+            actions_for(b"\x1b[1m\x1b[2m"),
+            vec![
+                Action::Csi { private: false, params: vec![1], final_byte: b'm' },
+                Action::Csi { private: false, params: vec![2], final_byte: b'm' },
+            ]
+        );
+    }
+}
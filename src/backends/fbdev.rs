@@ -2,9 +2,38 @@ use linuxfb::Framebuffer;
 use memmap::MmapMut;
 use os_terminal::{DrawTarget, Rgb};
 
+struct PixelFormat {
+    bytes_per_pixel: usize,
+    red_offset: u32,
+    red_len: u32,
+    green_offset: u32,
+    green_len: u32,
+    blue_offset: u32,
+    blue_len: u32,
+}
+
+impl PixelFormat {
+    fn scale(channel: u8, len: u32) -> u32 {
+        if len >= 8 {
+            (channel as u32) << (len - 8)
+        } else {
+            (channel as u32) >> (8 - len)
+        }
+    }
+
+    #[inline]
+    fn pack(&self, rgb: Rgb) -> u32 {
+        Self::scale(rgb.0, self.red_len) << self.red_offset
+            | Self::scale(rgb.1, self.green_len) << self.green_offset
+            | Self::scale(rgb.2, self.blue_len) << self.blue_offset
+    }
+}
+
 pub struct Display {
     width: usize,
     height: usize,
+    stride: usize,
+    format: PixelFormat,
     map: MmapMut,
 }
 
@@ -12,10 +41,26 @@ impl Display {
     pub fn new() -> Self {
         let fb = Framebuffer::new("/dev/fb0").expect("Failed to open fbdev");
         let (width, height) = fb.get_size();
+        let layout = fb.get_pixel_layout();
+        let bytes_per_pixel = fb.get_bytes_per_pixel() as usize;
+        let format = PixelFormat {
+            bytes_per_pixel,
+            red_offset: layout.red.offset,
+            red_len: layout.red.length,
+            green_offset: layout.green.offset,
+            green_len: layout.green.length,
+            blue_offset: layout.blue.offset,
+            blue_len: layout.blue.length,
+        };
+        // Fixed-screen-info stride: may exceed width * bytes_per_pixel on
+        // hardware that pads scanlines for alignment.
+        let stride = fb.get_line_length() as usize;
         let map = fb.map().expect("Failed to map fb");
         Self {
             width: width as usize,
             height: height as usize,
+            stride,
+            format,
             map,
         }
     }
@@ -28,8 +73,9 @@ impl DrawTarget for Display {
 
     #[inline]
     fn draw_pixel(&mut self, x: usize, y: usize, rgb: Rgb) {
-        let pixel = (rgb.0 as u32) << 16 | (rgb.1 as u32) << 8 | rgb.2 as u32;
-        let buffer = self.map.as_chunks_mut::<4>().0;
-        buffer[y * self.width + x].copy_from_slice(&pixel.to_ne_bytes());
+        let bpp = self.format.bytes_per_pixel;
+        let offset = y * self.stride + x * bpp;
+        let word = self.format.pack(rgb).to_ne_bytes();
+        self.map[offset..offset + bpp].copy_from_slice(&word[..bpp]);
     }
 }
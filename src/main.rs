@@ -1,6 +1,6 @@
 use crate::backends::Display;
 use anyhow::{Context, Result};
-use evdev::{Device, EventSummary, RelativeAxisCode};
+use evdev::{Device, EventSummary, KeyCode, RelativeAxisCode};
 use keycode::KeyMap;
 use nix::errno::Errno;
 use nix::libc::{TIOCSWINSZ, ioctl};
@@ -21,6 +21,12 @@ const DISPLAY_SIZE: (usize, usize) = (1024, 768);
 const VT_GETMODE: i32 = 0x5601;
 const VT_SETMODE: i32 = 0x5602;
 
+fn pixel_to_cell(x: isize, y: isize, columns: usize, rows: usize) -> (usize, usize) {
+    let col = (x as usize / (DISPLAY_SIZE.0 / columns)).min(columns - 1);
+    let row = (y as usize / (DISPLAY_SIZE.1 / rows)).min(rows - 1);
+    (col, row)
+}
+
 fn main() -> Result<()> {
     match unsafe { forkpty(None, None) }? {
         ForkptyResult::Child => {
@@ -32,6 +38,7 @@ fn main() -> Result<()> {
         ForkptyResult::Parent { child: _, master } => {
             let display = Display::new();
             let mut terminal = Terminal::new(display);
+            let clipboard = Arc::new(Mutex::new(String::new()));
 
             terminal.set_auto_flush(false);
             terminal.set_scroll_speed(5);
@@ -47,6 +54,33 @@ fn main() -> Result<()> {
             terminal.set_font_manager(Box::new(font_manager));
             terminal.set_history_size(1000);
 
+            let master_resize = master.try_clone()?;
+            terminal.set_resize_callback(Box::new(move |columns, rows| {
+                let win_size = Winsize {
+                    ws_row: rows as u16,
+                    ws_col: columns as u16,
+                    ws_xpixel: DISPLAY_SIZE.0 as u16,
+                    ws_ypixel: DISPLAY_SIZE.1 as u16,
+                };
+                unsafe { ioctl(master_resize.as_raw_fd(), TIOCSWINSZ, &win_size) };
+            }));
+
+            let clipboard_set = clipboard.clone();
+            terminal.set_clipboard_callback(Box::new(move |text| {
+                *clipboard_set.lock().unwrap() = text;
+            }));
+
+            let clipboard_query = clipboard.clone();
+            terminal.set_clipboard_query_callback(Box::new(move || {
+                clipboard_query.lock().unwrap().clone()
+            }));
+
+            // No window chrome to retitle on a bare fbdev console, so just
+            // surface OSC 0/2 title changes on stderr.
+            terminal.set_title_callback(Box::new(|title| {
+                eprintln!("title changed: {title}");
+            }));
+
             let win_size = Winsize {
                 ws_row: terminal.rows() as u16,
                 ws_col: terminal.columns() as u16,
@@ -116,13 +150,22 @@ fn main() -> Result<()> {
 
                     while let Ok(_) = flush_receiver.try_recv() {}
 
-                    terminal_clone.lock().unwrap().flush();
-                    last_flush = Instant::now();
+                    let mut term = terminal_clone.lock().unwrap();
+                    if term.frame_ready() {
+                        term.flush();
+                        last_flush = Instant::now();
+                    }
                 }
             });
 
             let mut kbd_evdev = Device::open("/dev/input/event0")?;
             let mut mouse_evdev_option = Device::open("/dev/input/event1").ok();
+
+            let mut mouse_x = (DISPLAY_SIZE.0 / 2) as isize;
+            let mut mouse_y = (DISPLAY_SIZE.1 / 2) as isize;
+            let mut dragging = false;
+            let mut shift_pressed = false;
+            let mut ctrl_pressed = false;
             loop {
                 for event in kbd_evdev.fetch_events()? {
                     let EventSummary::Key(_, code, press) = event.destructure() else {
@@ -134,6 +177,41 @@ fn main() -> Result<()> {
                         continue;
                     };
 
+                    match keymap.win {
+                        0x2a | 0x36 => shift_pressed = press != 0,
+                        0x1d | 0xe01d => ctrl_pressed = press != 0,
+                        0x0d if ctrl_pressed => {
+                            if press == 1 {
+                                terminal.lock().unwrap().increase_font_size();
+                                let _ = flush_sender.send(());
+                            }
+                            continue;
+                        }
+                        0x0c if ctrl_pressed => {
+                            if press == 1 {
+                                terminal.lock().unwrap().decrease_font_size();
+                                let _ = flush_sender.send(());
+                            }
+                            continue;
+                        }
+                        0x0b if ctrl_pressed => {
+                            if press == 1 {
+                                terminal.lock().unwrap().set_font_size(10.0);
+                                let _ = flush_sender.send(());
+                            }
+                            continue;
+                        }
+                        0xe052 if shift_pressed => {
+                            if press == 1 {
+                                let text = clipboard.lock().unwrap().clone();
+                                terminal.lock().unwrap().paste(&text);
+                                let _ = flush_sender.send(());
+                            }
+                            continue;
+                        }
+                        _ => {}
+                    }
+
                     let mut scancode = keymap.win;
                     if press == 0 {
                         scancode += 0x80;
@@ -154,15 +232,50 @@ fn main() -> Result<()> {
                 };
 
                 for event in mouse_evdev.fetch_events()? {
-                    let EventSummary::RelativeAxis(_, code, value) = event.destructure() else {
-                        continue;
-                    };
+                    match event.destructure() {
+                        EventSummary::RelativeAxis(_, code, value) => match code {
+                            RelativeAxisCode::REL_X => {
+                                mouse_x = (mouse_x + value as isize).clamp(0, DISPLAY_SIZE.0 as isize - 1);
+                            }
+                            RelativeAxisCode::REL_Y => {
+                                mouse_y = (mouse_y + value as isize).clamp(0, DISPLAY_SIZE.1 as isize - 1);
+                            }
+                            RelativeAxisCode::REL_WHEEL => {
+                                terminal
+                                    .lock()
+                                    .unwrap()
+                                    .handle_mouse(MouseInput::Scroll(value as isize));
+                                let _ = flush_sender.send(());
+                                continue;
+                            }
+                            _ => continue,
+                        },
+                        EventSummary::Key(_, code, value) if code == KeyCode::BTN_LEFT => {
+                            let mut term = terminal.lock().unwrap();
+                            let (col, row) = pixel_to_cell(mouse_x, mouse_y, term.columns(), term.rows());
+                            if value == 1 {
+                                dragging = true;
+                                term.handle_mouse(MouseInput::Press(col, row));
+                            } else if value == 0 {
+                                dragging = false;
+                                term.handle_mouse(MouseInput::Release);
+                                if !term.mouse_tracking() || shift_pressed {
+                                    let text = term.selection_text();
+                                    if !text.is_empty() {
+                                        *clipboard.lock().unwrap() = text;
+                                    }
+                                }
+                            }
+                            let _ = flush_sender.send(());
+                            continue;
+                        }
+                        _ => continue,
+                    }
 
-                    if code == RelativeAxisCode::REL_WHEEL {
-                        terminal
-                            .lock()
-                            .unwrap()
-                            .handle_mouse(MouseInput::Scroll(value as isize));
+                    let mut term = terminal.lock().unwrap();
+                    if dragging || term.mouse_tracking() {
+                        let (col, row) = pixel_to_cell(mouse_x, mouse_y, term.columns(), term.rows());
+                        term.handle_mouse(MouseInput::Move(col, row));
                         let _ = flush_sender.send(());
                     }
                 }